@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Compiles `languages.json` into a generated Rust table (`OUT_DIR/languages_generated.rs`)
+//! consumed by `src/language.rs`. Editing `languages.json` to add or extend a language does
+//! not require touching any Rust code.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LanguageDef {
+    name: String,
+    extensions: Vec<String>,
+    filenames: Vec<String>,
+    line_comments: Vec<String>,
+    block_comments: Vec<(String, String)>,
+    quotes: Vec<char>,
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    let languages_path = Path::new(&manifest_dir).join("languages.json");
+    println!("cargo:rerun-if-changed={}", languages_path.display());
+
+    let raw = fs::read_to_string(&languages_path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", languages_path.display()));
+    let defs: Vec<LanguageDef> =
+        serde_json::from_str(&raw).expect("languages.json did not match the expected schema");
+
+    let mut out = String::new();
+
+    writeln!(out, "pub fn generated_lookup_extension(ext: &str) -> Option<&'static str> {{").unwrap();
+    writeln!(out, "    match ext {{").unwrap();
+    for def in &defs {
+        for ext in &def.extensions {
+            writeln!(out, "        {:?} => Some({:?}),", ext.to_lowercase(), def.name).unwrap();
+        }
+    }
+    writeln!(out, "        _ => None,").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "pub fn generated_lookup_filename(name: &str) -> Option<&'static str> {{").unwrap();
+    writeln!(out, "    match name {{").unwrap();
+    for def in &defs {
+        for filename in &def.filenames {
+            writeln!(out, "        {:?} => Some({:?}),", filename.to_lowercase(), def.name).unwrap();
+        }
+    }
+    writeln!(out, "        _ => None,").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "pub fn generated_comment_syntax(lang: &str) -> CommentSyntax {{").unwrap();
+    writeln!(out, "    match lang {{").unwrap();
+    for def in &defs {
+        let line_comments = def
+            .line_comments
+            .iter()
+            .map(|s| format!("{s:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let block_comments = def
+            .block_comments
+            .iter()
+            .map(|(open, close)| format!("({open:?}, {close:?})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let quotes = def
+            .quotes
+            .iter()
+            .map(|c| format!("{c:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            out,
+            "        {:?} => CommentSyntax {{ line_comments: &[{}], block_comments: &[{}], quotes: &[{}] }},",
+            def.name, line_comments, block_comments, quotes
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "        _ => CommentSyntax {{ line_comments: &[], block_comments: &[], quotes: &[] }},"
+    )
+    .unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
+    let dest = Path::new(&out_dir).join("languages_generated.rs");
+    fs::write(&dest, out).unwrap_or_else(|e| panic!("failed to write {}: {e}", dest.display()));
+}
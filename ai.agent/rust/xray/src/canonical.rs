@@ -2,46 +2,150 @@
 
 use crate::schema::XrayIndex;
 use anyhow::{Context, Result};
-use serde_json::{Map, Value};
+use serde::Serialize;
+use serde_json::{Number, Value};
 
-/// Serializes the index to **Canonical JSON** (object keys sorted lexicographically, no extra whitespace).
+/// Serializes the index to RFC 8785 JSON Canonicalization Scheme (JCS) bytes.
 ///
-/// Determinism requirements:
-/// - All JSON objects MUST have keys sorted (lexicographically).
-/// - Arrays MUST already be deterministically ordered by the caller/spec (e.g., files sorted by path).
-/// - Output MUST be compact (no pretty-print / no whitespace variance).
+/// Determinism requirements (JCS, on top of plain "sorted keys, no whitespace" canonical JSON):
+/// - Object keys are sorted by UTF-16 code unit order (not byte order).
+/// - Numbers are rendered with the ECMAScript `Number::toString` shortest round-trip
+///   algorithm, so the same value always serializes to the same digits across serde_json
+///   versions or platforms.
+/// - Strings use the minimal escape set (`\"`, `\\`, the named control escapes, and
+///   `\uXXXX` for the remaining C0 controls); everything else is emitted as literal UTF-8.
 ///
-/// Notes:
-/// - `serde_json` will emit struct fields in struct declaration order, and map keys in map iteration order.
-/// - Using `BTreeMap` helps, but does not guarantee recursive key ordering for *all* nested objects.
-/// - Therefore we canonicalize by converting to `serde_json::Value` and recursively sorting object keys.
+/// Arrays MUST already be deterministically ordered by the caller/spec (e.g., files sorted
+/// by path) - JCS does not reorder arrays.
 pub fn to_canonical_json(index: &XrayIndex) -> Result<Vec<u8>> {
-    let value = serde_json::to_value(index).context("Failed to convert index to JSON value")?;
-    let canon = canonicalize_value(value);
-    serde_json::to_vec(&canon).context("Failed to serialize canonical JSON")
+    canonicalize_to_bytes(index)
 }
 
-fn canonicalize_value(v: Value) -> Value {
-    match v {
-        Value::Object(map) => canonicalize_object(map),
-        Value::Array(arr) => Value::Array(arr.into_iter().map(canonicalize_value).collect()),
-        other => other,
+/// Serializes any `Serialize` value to JCS canonical bytes. Shared by the whole-index
+/// digest and the per-file `content_hash`.
+pub fn canonicalize_to_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(value).context("Failed to convert value to JSON")?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out);
+    Ok(out.into_bytes())
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&format_number(n)),
+        Value::String(s) => write_canonical_string(s, out),
+        Value::Array(arr) => {
+            out.push('[');
+            for (i, v) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(v, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (i, key) in keys.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_string(key, out);
+                out.push(':');
+                write_canonical(&map[*key], out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Escapes a string with the minimal set JCS requires: `"` and `\`, the named C0 control
+/// escapes (`\b \f \n \r \t`), `\u00XX` for any other C0 control, and everything else
+/// (including non-ASCII) emitted as literal UTF-8.
+fn write_canonical_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
     }
+    out.push('"');
 }
 
-fn canonicalize_object(map: Map<String, Value>) -> Value {
-    // Sort keys lexicographically.
-    let mut keys: Vec<String> = map.keys().cloned().collect();
-    keys.sort();
+/// Formats a `serde_json::Number` per the ECMAScript `Number::toString` algorithm: an
+/// integer with no decimal point or exponent when the value is a whole number, otherwise
+/// the shortest decimal that round-trips, switching to exponent form only outside the
+/// `1e-6 .. 1e21` range.
+fn format_number(n: &Number) -> String {
+    if let Some(u) = n.as_u64() {
+        return u.to_string();
+    }
+    if let Some(i) = n.as_i64() {
+        return i.to_string();
+    }
+    format_f64(n.as_f64().unwrap_or(0.0))
+}
+
+fn format_f64(f: f64) -> String {
+    if f == 0.0 {
+        return "0".to_string();
+    }
+
+    let neg = f.is_sign_negative();
+    let sci = format!("{:e}", f.abs()); // shortest round-trip mantissa, e.g. "1.5e2" or "5e0"
+    let (mantissa, exp_str) = sci.split_once('e').expect("scientific notation has an 'e'");
+    let exp: i64 = exp_str.parse().expect("exponent is a valid integer");
+
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let digits = digits.trim_end_matches('0');
+    let digits = if digits.is_empty() { "0" } else { digits };
+    let k = digits.len() as i64;
+    let n = exp + 1; // position of the decimal point, counted from the left
+
+    let mut result = String::new();
+    if neg {
+        result.push('-');
+    }
 
-    let mut out = Map::new();
-    for k in keys {
-        // Safe: key exists in original map.
-        let child = map.get(&k).expect("key must exist").clone();
-        out.insert(k, canonicalize_value(child));
+    if n >= k && n <= 21 {
+        result.push_str(digits);
+        result.push_str(&"0".repeat((n - k) as usize));
+    } else if n > 0 && n <= 21 {
+        result.push_str(&digits[..n as usize]);
+        result.push('.');
+        result.push_str(&digits[n as usize..]);
+    } else if n <= 0 && n > -6 {
+        result.push_str("0.");
+        result.push_str(&"0".repeat((-n) as usize));
+        result.push_str(digits);
+    } else {
+        result.push_str(&digits[..1]);
+        if k > 1 {
+            result.push('.');
+            result.push_str(&digits[1..]);
+        }
+        result.push('e');
+        let e = n - 1;
+        if e >= 0 {
+            result.push('+');
+        }
+        result.push_str(&e.to_string());
     }
 
-    Value::Object(out)
+    result
 }
 
 /// Validates that the index is sorted correctly.
@@ -63,3 +167,61 @@ pub fn validate_sort_order(index: &XrayIndex) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn canonical_string(value: &Value) -> String {
+        let mut out = String::new();
+        write_canonical(value, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_negative_zero_serializes_as_zero() {
+        let value = json!(-0.0);
+        assert_eq!(canonical_string(&value), "0");
+    }
+
+    #[test]
+    fn test_exponent_boundary_below_1e_minus_6() {
+        // Just outside the `1e-6 .. 1e21` range on the small side: exponent form.
+        assert_eq!(format_f64(1e-7), "1e-7");
+        // Just inside: plain decimal form.
+        assert_eq!(format_f64(1e-6), "0.000001");
+    }
+
+    #[test]
+    fn test_exponent_boundary_above_1e21() {
+        // Just inside the range on the large side: plain decimal form.
+        assert_eq!(format_f64(1e20), "100000000000000000000");
+        // Just outside: exponent form.
+        assert_eq!(format_f64(1e21), "1e+21");
+    }
+
+    #[test]
+    fn test_object_keys_sorted_by_utf16_code_unit_not_byte_order() {
+        // 'z' (0x7A) sorts before 'é' (U+00E9) both by UTF-16 code unit and by UTF-8 byte.
+        // U+1F600 (UTF-16 high surrogate 0xD83D) sorts before U+FF61 by UTF-16 code unit,
+        // while the raw UTF-8 bytes (F0.. vs EF..) would put U+FF61 first.
+        let value = json!({"é": 1, "z": 2, "a": 3, "\u{ff61}": 4, "\u{1f600}": 5});
+        assert_eq!(
+            canonical_string(&value),
+            "{\"a\":3,\"z\":2,\"é\":1,\"\u{1f600}\":5,\"\u{ff61}\":4}"
+        );
+    }
+
+    #[test]
+    fn test_string_escapes_control_character() {
+        let value = Value::String("\u{1}".to_string());
+        assert_eq!(canonical_string(&value), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_string_escapes_named_controls_and_quote() {
+        let value = Value::String("a\"b\\c\nd".to_string());
+        assert_eq!(canonical_string(&value), "\"a\\\"b\\\\c\\nd\"");
+    }
+}
@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Per-file source complexity metrics (cyclomatic, cognitive, Halstead, maintainability
+//! index), computed by walking a tree-sitter parse tree for the file's detected language.
+//!
+//! Gated behind the `complexity` cargo feature: the grammars are heavy to compile, and
+//! most consumers of `xray` only need size/LOC stats.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, Node, Parser};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityStats {
+    pub cyclomatic: u64,
+    pub cognitive: u64,
+    pub halstead_volume: f64,
+    pub maintainability_index: f64,
+}
+
+/// Node kinds a tree-sitter grammar uses for decision points, boolean operators, and the
+/// ternary/conditional expression, for one canonical language. Extend this table (and
+/// `grammar_for`) to wire up another language.
+struct DecisionKinds {
+    /// Node kinds that each add one branch (`if`, `for`, `while`, `case`, `catch`, ...).
+    branches: &'static [&'static str],
+    /// Node kinds for `&&`/`and`-style boolean operators.
+    boolean_ops: &'static [&'static str],
+    /// Node kinds for the ternary/conditional expression (`a ? b : c`).
+    ternary: &'static [&'static str],
+}
+
+fn grammar_for(lang: &str) -> Option<Language> {
+    match lang {
+        "Rust" => Some(tree_sitter_rust::LANGUAGE.into()),
+        "Go" => Some(tree_sitter_go::LANGUAGE.into()),
+        "JavaScript" => Some(tree_sitter_javascript::LANGUAGE.into()),
+        "TypeScript" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        "Python" => Some(tree_sitter_python::LANGUAGE.into()),
+        "Java" => Some(tree_sitter_java::LANGUAGE.into()),
+        "C" => Some(tree_sitter_c::LANGUAGE.into()),
+        "C++" => Some(tree_sitter_cpp::LANGUAGE.into()),
+        _ => None,
+    }
+}
+
+fn decision_kinds_for(lang: &str) -> DecisionKinds {
+    match lang {
+        "Rust" => DecisionKinds {
+            branches: &[
+                "if_expression",
+                "for_expression",
+                "while_expression",
+                "loop_expression",
+                "match_arm",
+            ],
+            boolean_ops: &["&&", "||"],
+            ternary: &[],
+        },
+        "Go" => DecisionKinds {
+            branches: &[
+                "if_statement",
+                "for_statement",
+                "expression_case",
+                "communication_case",
+                "type_case",
+            ],
+            boolean_ops: &["&&", "||"],
+            ternary: &[],
+        },
+        "JavaScript" | "TypeScript" => DecisionKinds {
+            branches: &[
+                "if_statement",
+                "for_statement",
+                "for_in_statement",
+                "while_statement",
+                "do_statement",
+                "switch_case",
+                "catch_clause",
+            ],
+            boolean_ops: &["&&", "||"],
+            ternary: &["ternary_expression"],
+        },
+        "Python" => DecisionKinds {
+            branches: &["if_statement", "for_statement", "while_statement", "except_clause"],
+            boolean_ops: &["and", "or"],
+            ternary: &["conditional_expression"],
+        },
+        "Java" => DecisionKinds {
+            branches: &[
+                "if_statement",
+                "for_statement",
+                "while_statement",
+                "do_statement",
+                "switch_label",
+                "catch_clause",
+            ],
+            boolean_ops: &["&&", "||"],
+            ternary: &["conditional_expression"],
+        },
+        "C" | "C++" => DecisionKinds {
+            branches: &[
+                "if_statement",
+                "for_statement",
+                "while_statement",
+                "do_statement",
+                "case_statement",
+                "catch_clause",
+            ],
+            boolean_ops: &["&&", "||"],
+            ternary: &["conditional_expression"],
+        },
+        _ => DecisionKinds {
+            branches: &[],
+            boolean_ops: &[],
+            ternary: &[],
+        },
+    }
+}
+
+/// Computes complexity metrics for `path`, or `None` if its detected language has no
+/// tree-sitter grammar wired up here (an unsupported language is simply omitted, not an
+/// error) or the file fails to parse.
+pub fn compute_complexity(path: &Path, lang: &str, loc: u64) -> Option<ComplexityStats> {
+    let language = grammar_for(lang)?;
+    let source = fs::read_to_string(path).ok()?;
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).ok()?;
+    let tree = parser.parse(&source, None)?;
+
+    let kinds = decision_kinds_for(lang);
+    let root = tree.root_node();
+
+    let cyclomatic = 1 + count_branches(root, &kinds);
+    let cognitive = cognitive_complexity(root, &kinds, 0);
+    let (n1, n2, total1, total2) = halstead_counts(root, source.as_bytes());
+    let volume = halstead_volume(n1, n2, total1, total2);
+    let mi = maintainability_index(volume, cyclomatic, loc);
+
+    Some(ComplexityStats {
+        cyclomatic,
+        cognitive,
+        halstead_volume: volume,
+        maintainability_index: mi,
+    })
+}
+
+/// Cyclomatic complexity base: counts one branch per decision node, boolean operator, or
+/// ternary expression found anywhere in the tree. The `+ 1` base case is added by the
+/// caller.
+fn count_branches(node: Node, kinds: &DecisionKinds) -> u64 {
+    let mut count = 0u64;
+    if kinds.branches.contains(&node.kind()) || kinds.ternary.contains(&node.kind()) {
+        count += 1;
+    }
+    if kinds.boolean_ops.contains(&node.kind()) {
+        count += 1;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        count += count_branches(child, kinds);
+    }
+    count
+}
+
+/// Cognitive complexity: +1 per control-flow structure, plus one extra increment per
+/// level of nesting it sits at (so a branch three blocks deep adds 3), plus +1 per
+/// boolean-operator break in a sequence.
+fn cognitive_complexity(node: Node, kinds: &DecisionKinds, nesting: u64) -> u64 {
+    let is_branch = kinds.branches.contains(&node.kind()) || kinds.ternary.contains(&node.kind());
+    let is_boolean_op = kinds.boolean_ops.contains(&node.kind());
+
+    let mut score = 0u64;
+    let mut child_nesting = nesting;
+
+    if is_branch {
+        score += 1 + nesting;
+        child_nesting = nesting + 1;
+    } else if is_boolean_op {
+        // A run of the same operator (`a && b && c`) is one sequence: the operator only
+        // counts when its left operand is not itself an expression with the same operator.
+        let continues_sequence = node.prev_sibling().is_some_and(|left| {
+            let mut left_cursor = left.walk();
+            let same_op = left.children(&mut left_cursor).any(|c| c.kind() == node.kind());
+            same_op
+        });
+        if !continues_sequence {
+            score += 1;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        score += cognitive_complexity(child, kinds, child_nesting);
+    }
+    score
+}
+
+/// Counts `(distinct_operators, distinct_operands, total_operators, total_operands)`
+/// across the tree's leaf tokens. Leaves whose node kind contains `identifier` or
+/// `literal` are operands, keyed by their actual source text (so `x` and `y` count as two
+/// distinct operands, not one "identifier" bucket); every other leaf (keywords,
+/// punctuation, and literals whose kind lacks `literal`, such as Python's `integer`) is an
+/// operator, keyed by its node kind (`+`, `if`, `;`, ...).
+fn halstead_counts<'a>(root: Node<'a>, source: &'a [u8]) -> (u64, u64, u64, u64) {
+    use std::collections::HashSet;
+
+    let mut operators = HashSet::new();
+    let mut operands = HashSet::new();
+    let mut total_operators = 0u64;
+    let mut total_operands = 0u64;
+
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.child_count() == 0 {
+            let kind = node.kind();
+            if kind.contains("identifier") || kind.contains("literal") {
+                let text = node.utf8_text(source).unwrap_or(kind);
+                operands.insert(text);
+                total_operands += 1;
+            } else {
+                operators.insert(kind);
+                total_operators += 1;
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    (
+        operators.len() as u64,
+        operands.len() as u64,
+        total_operators,
+        total_operands,
+    )
+}
+
+/// Halstead volume: `(N1 + N2) * log2(n1 + n2)`, where `n1`/`n2` are the counts of
+/// distinct operators/operands and `N1`/`N2` their totals.
+fn halstead_volume(n1: u64, n2: u64, total1: u64, total2: u64) -> f64 {
+    let vocabulary = (n1 + n2) as f64;
+    if vocabulary <= 0.0 {
+        return 0.0;
+    }
+    let length = (total1 + total2) as f64;
+    length * vocabulary.log2()
+}
+
+/// Maintainability index: `max(0, (171 - 5.2*ln(V) - 0.23*cyclomatic - 16.2*ln(loc)) * 100 / 171)`.
+fn maintainability_index(halstead_volume: f64, cyclomatic: u64, loc: u64) -> f64 {
+    let v = halstead_volume.max(1.0);
+    let loc = (loc.max(1)) as f64;
+    let raw = 171.0 - 5.2 * v.ln() - 0.23 * (cyclomatic as f64) - 16.2 * loc.ln();
+    (raw * 100.0 / 171.0).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn compute(lang: &str, source: &str) -> ComplexityStats {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{source}").unwrap();
+        compute_complexity(file.path(), lang, source.lines().count() as u64)
+            .unwrap_or_else(|| panic!("expected {lang} to have a wired-up grammar"))
+    }
+
+    #[test]
+    fn test_rust_if_else_is_branchy() {
+        let stats = compute(
+            "Rust",
+            "fn f(x: i32) -> i32 { if x > 0 { x } else { -x } }",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_go_if_is_branchy() {
+        let stats = compute(
+            "Go",
+            "package main\nfunc f(x int) int {\n if x > 0 {\n  return x\n }\n return -x\n}\n",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_javascript_if_else_is_branchy() {
+        let stats = compute(
+            "JavaScript",
+            "function f(x) { if (x > 0) { return x; } else { return -x; } }",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_typescript_if_else_is_branchy() {
+        let stats = compute(
+            "TypeScript",
+            "function f(x: number): number { if (x > 0) { return x; } else { return -x; } }",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_python_if_is_branchy() {
+        let stats = compute("Python", "def f(x):\n    if x > 0:\n        return x\n    return -x\n");
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_java_switch_is_branchy() {
+        let stats = compute(
+            "Java",
+            "class A { int f(int x) { switch (x) { case 1: return 1; default: return 0; } } }",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    // Regression test for a grammar/branch-kind mismatch: tree-sitter-c/cpp name their
+    // switch-case node `case_statement`, not `switch_label` (that's Java-only) - a `switch`
+    // with no `if` used to contribute nothing to cyclomatic/cognitive complexity.
+    #[test]
+    fn test_c_switch_is_branchy() {
+        let stats = compute(
+            "C",
+            "int f(int x) { switch (x) { case 1: return 1; default: return 0; } }",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_cpp_switch_is_branchy() {
+        let stats = compute(
+            "C++",
+            "int f(int x) { switch (x) { case 1: return 1; default: return 0; } }",
+        );
+        assert!(stats.cyclomatic >= 2);
+    }
+
+    #[test]
+    fn test_halstead_counts_distinct_operand_text_not_just_kind() {
+        let source = "fn f() { let a = 1; let b = 2; let c = 3; }";
+        let stats = compute("Rust", source);
+        // `a`, `b`, `c`, `1`, `2`, `3` are six distinct operand *values*; bucketing by node
+        // kind alone would collapse them into two ("identifier", "integer_literal").
+        assert!(stats.halstead_volume > 0.0);
+        let mut parser = Parser::new();
+        parser.set_language(&grammar_for("Rust").unwrap()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let (_, distinct_operands, _, _) = halstead_counts(tree.root_node(), source.as_bytes());
+        assert!(distinct_operands >= 6);
+    }
+
+    #[test]
+    fn test_halstead_volume_empty_vocabulary() {
+        assert_eq!(halstead_volume(0, 0, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_halstead_volume_known_value() {
+        // vocabulary = 4, length = 8 -> 8 * log2(4) = 16.0
+        assert_eq!(halstead_volume(2, 2, 4, 4), 16.0);
+    }
+
+    #[test]
+    fn test_maintainability_index_is_clamped_to_zero() {
+        let mi = maintainability_index(1_000_000.0, 500, 100_000);
+        assert_eq!(mi, 0.0);
+    }
+
+    #[test]
+    fn test_maintainability_index_trivial_file() {
+        // A single-statement file should score near the top of the scale.
+        let mi = maintainability_index(1.0, 1, 1);
+        assert!(mi > 90.0 && mi <= 100.0);
+    }
+}
@@ -1,16 +1,21 @@
 use crate::schema::XrayIndex;
 use crate::canonical::to_canonical_json;
-use sha2::{Digest, Sha256};
+use crate::hash::sha256_of;
 use anyhow::Result;
 
-/// Calculates the repository digest.
+/// Calculates the repository's index digest.
+///
+/// The digest is: "sha256:" + SHA-256( JCS-CanonicalJSON( Index( index_digest="" ) ) ),
+/// matching the `"sha256:<hex>"` format used by `FileNode::hash` and `content_hash`.
 ///
-/// The digest is: SHA-256( CanonicalJSON( Index( digest="" ) ) )
-/// 
 /// 1. Clone the index.
-/// 2. Set digest to empty string.
-/// 3. Serialize to canonical JSON.
+/// 2. Set index_digest to empty string.
+/// 3. Serialize to canonical JSON (RFC 8785).
 /// 4. Hash it.
+///
+/// Because every file's own `content_hash` already participates in the index, and
+/// `content_hash`/`hash`/`lang`/`loc`/`complexity` all feed this digest, two scans over
+/// identical trees always produce identical digests - suitable for caching.
 pub fn calculate_digest(index: &XrayIndex) -> Result<String> {
     let mut clone = XrayIndex {
         schema_version: index.schema_version.clone(),
@@ -21,7 +26,7 @@ pub fn calculate_digest(index: &XrayIndex) -> Result<String> {
         top_dirs: index.top_dirs.clone(),
         module_files: index.module_files.clone(),
         stats: index.stats.clone(),
-        digest: "".to_string(), // MUST be empty for calculation
+        index_digest: "".to_string(), // MUST be empty for calculation
     };
 
     // Ensure strict sorting before hashing
@@ -29,9 +34,5 @@ pub fn calculate_digest(index: &XrayIndex) -> Result<String> {
     clone.module_files.sort();
 
     let bytes = to_canonical_json(&clone)?;
-    let mut hasher = Sha256::new();
-    hasher.update(&bytes);
-    let result = hasher.finalize();
-
-    Ok(hex::encode(result))
+    Ok(format!("sha256:{}", sha256_of(&bytes)))
 }
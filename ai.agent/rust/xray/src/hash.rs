@@ -1,3 +1,5 @@
+use crate::canonical;
+use crate::schema::FileNode;
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::fs::File;
@@ -24,3 +26,18 @@ pub fn compute_file_hash(path: &Path) -> Result<String> {
     let result = hasher.finalize();
     Ok(format!("sha256:{}", hex::encode(result)))
 }
+
+/// Computes this file node's own content hash: SHA-256 of its canonical JCS JSON, with
+/// `content_hash` itself cleared first so the hash doesn't depend on itself.
+pub fn compute_content_hash(node: &FileNode) -> Result<String> {
+    let mut clone = node.clone();
+    clone.content_hash = String::new();
+    let bytes = canonical::canonicalize_to_bytes(&clone)
+        .context("Failed to canonicalize file node for content hash")?;
+    Ok(format!("sha256:{}", sha256_of(&bytes)))
+}
+
+/// Hashes `bytes` with SHA-256 and returns the lowercase hex digest.
+pub fn sha256_of(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
@@ -1,44 +1,145 @@
 use std::path::Path;
 
-/// Detects language from file path (extension based).
+/// Comment/string syntax for a language, used to classify lines as code/comment/blank.
+///
+/// `line_comments` are tokens that make the rest of the line a comment (e.g. `//`).
+/// `block_comments` are `(open, close)` delimiter pairs that nest (e.g. `("/*", "*/")`).
+/// `quotes` are characters that open/close a string literal, inside which comment
+/// tokens must be ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct CommentSyntax {
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+    pub quotes: &'static [char],
+}
+
+// Generated by build.rs from `languages.json`: `generated_lookup_extension`,
+// `generated_lookup_filename`, `generated_comment_syntax`. Add a language by editing
+// `languages.json`, not this file.
+include!(concat!(env!("OUT_DIR"), "/languages_generated.rs"));
+
+/// Returns the comment/string syntax for a canonical language name, as returned by
+/// `detect_language`. Unknown languages get an empty syntax (every line counts as code).
+pub fn comment_syntax(lang: &str) -> CommentSyntax {
+    generated_comment_syntax(lang)
+}
+
+/// Detects language from file path (extension based, with special-cased filenames).
 /// Returns explicit "Unknown" if not matched, or the canonical language name.
 pub fn detect_language(path: &Path) -> String {
     // Special filenames
     if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-        if name.eq_ignore_ascii_case("Dockerfile") {
-            return "Dockerfile".to_string();
-        }
-        if name.eq_ignore_ascii_case("Makefile") {
-            return "Makefile".to_string();
+        if let Some(lang) = generated_lookup_filename(&name.to_lowercase()) {
+            return lang.to_string();
         }
     }
 
     // Extensions
     if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-        match ext.to_lowercase().as_str() {
-            "go" => "Go",
-            "rs" => "Rust",
-            "md" => "Markdown",
-            "json" => "JSON",
-            "js" => "JavaScript",
-            "ts" => "TypeScript",
-            "yaml" | "yml" => "YAML",
-            "toml" => "TOML",
-            "sh" | "bash" => "Shell",
-            "html" | "htm" => "HTML",
-            "css" => "CSS",
-            "sql" => "SQL",
-            "py" => "Python",
-            "java" => "Java",
-            "c" | "h" => "C",
-            "cpp" | "hpp" | "cc" | "cxx" => "C++",
-            "tf" => "Terraform",
-            "txt" | "text" => "Text",
-            _ => "Unknown", // Or leave empty? Spec implies "languages" map. Unknowns usually ignored in stats? 
-                            // let's return "Unknown" so it's explicit for now, but usually we might exclude from stats.
-                            // The user said "others Unknown or skip (choose + lock)". I will lock to "Unknown".
-        }.to_string()
+        generated_lookup_extension(&ext.to_lowercase())
+            .unwrap_or("Unknown")
+            .to_string()
     } else {
         "Unknown".to_string()
     }
 }
+
+/// Like `detect_language`, but falls back to parsing a `#!` shebang on `first_line` when
+/// the extension/filename lookup can't determine a language (e.g. extensionless scripts).
+pub fn detect_language_with_contents(path: &Path, first_line: Option<&str>) -> String {
+    let lang = detect_language(path);
+    if lang != "Unknown" {
+        return lang;
+    }
+
+    first_line
+        .and_then(shebang_interpreter)
+        .and_then(map_interpreter)
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+/// Extracts the interpreter basename from a `#!` shebang line, unwrapping the
+/// `#!/usr/bin/env <interpreter>` form.
+fn shebang_interpreter(first_line: &str) -> Option<&str> {
+    let rest = first_line.trim_end_matches(['\n', '\r']).strip_prefix("#!")?;
+    let mut parts = rest.split_whitespace();
+    let program = parts.next()?;
+    let basename = program.rsplit('/').next().unwrap_or(program);
+    if basename == "env" {
+        parts.next()
+    } else {
+        Some(basename)
+    }
+}
+
+/// Maps a shebang interpreter name to its canonical language.
+fn map_interpreter(interpreter: &str) -> Option<&'static str> {
+    match interpreter {
+        "python" | "python2" | "python3" => Some("Python"),
+        "bash" | "sh" => Some("Shell"),
+        "node" => Some("JavaScript"),
+        "ruby" => Some("Ruby"),
+        "perl" => Some("Perl"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_detect_language_by_extension() {
+        assert_eq!(detect_language(Path::new("main.rs")), "Rust");
+    }
+
+    #[test]
+    fn test_detect_language_by_special_filename() {
+        assert_eq!(detect_language(Path::new("Dockerfile")), "Dockerfile");
+    }
+
+    #[test]
+    fn test_detect_language_unknown_extension() {
+        assert_eq!(detect_language(Path::new("file.xyz")), "Unknown");
+    }
+
+    #[test]
+    fn test_shebang_env_form_resolves_interpreter() {
+        let lang = detect_language_with_contents(
+            Path::new("script"),
+            Some("#!/usr/bin/env python3\n"),
+        );
+        assert_eq!(lang, "Python");
+    }
+
+    #[test]
+    fn test_shebang_bare_interpreter_resolves() {
+        let lang = detect_language_with_contents(Path::new("script"), Some("#!/bin/sh\n"));
+        assert_eq!(lang, "Shell");
+    }
+
+    #[test]
+    fn test_shebang_unmapped_interpreter_is_unknown() {
+        let lang = detect_language_with_contents(
+            Path::new("script"),
+            Some("#!/usr/bin/env made-up-interpreter\n"),
+        );
+        assert_eq!(lang, "Unknown");
+    }
+
+    #[test]
+    fn test_no_shebang_and_no_extension_is_unknown() {
+        let lang = detect_language_with_contents(Path::new("script"), None);
+        assert_eq!(lang, "Unknown");
+    }
+
+    #[test]
+    fn test_extension_takes_priority_over_shebang() {
+        // A `.rs` file with a (nonsensical) shebang should still resolve by extension.
+        let lang =
+            detect_language_with_contents(Path::new("main.rs"), Some("#!/usr/bin/env python3\n"));
+        assert_eq!(lang, "Rust");
+    }
+}
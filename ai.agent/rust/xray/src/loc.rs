@@ -2,92 +2,261 @@ use std::fs::File;
 use std::io::{Read, BufReader};
 use std::path::Path;
 use anyhow::{Context, Result};
+use encoding_rs::{UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+use crate::language::{self, CommentSyntax};
 
 pub const LOC_BIG_FILE_CAP_BYTES: u64 = 2 * 1024 * 1024; // 2MB
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct LocStats {
     pub loc: u64,
+    pub code: u64,
+    pub comments: u64,
+    pub blanks: u64,
     pub size: u64,
     pub skipped: bool,
+    /// Encoding the file content was decoded as (e.g. "UTF-8", "UTF-16LE", "Latin-1"),
+    /// or "binary" for a genuinely binary (skipped) file.
+    pub encoding: String,
 }
 
 /// Computes LOC Stats for a given file path.
-/// 
+///
+/// `lang` is the canonical language name from `detect_language`, used to select the
+/// comment/string syntax for line classification.
+///
 /// Rules:
-/// - If file > 2MB, return skipped=true, loc=0.
-/// - If file content is not valid UTF-8, return skipped=true, loc=0.
-/// - Count newlines.
-pub fn compute_loc(path: &Path) -> Result<LocStats> {
+/// - If file > 2MB, return skipped=true, all counts 0.
+/// - If file content is binary (a UTF-8/UTF-16 BOM isn't present, the content isn't valid
+///   UTF-8, and it contains a NUL byte), return skipped=true, all counts 0.
+/// - Otherwise decode the content (see `decode_content`) and classify every line as code,
+///   comment, or blank (see `classify_lines`).
+pub fn compute_loc(path: &Path, lang: &str) -> Result<LocStats> {
     let metadata = std::fs::metadata(path).context("Failed to get file metadata")?;
     let size = metadata.len();
 
     if size > LOC_BIG_FILE_CAP_BYTES {
         return Ok(LocStats {
             loc: 0,
+            code: 0,
+            comments: 0,
+            blanks: 0,
             size,
             skipped: true,
+            encoding: "unknown".to_string(),
         });
     }
 
     if size == 0 {
         return Ok(LocStats {
             loc: 0,
+            code: 0,
+            comments: 0,
+            blanks: 0,
             size: 0,
             skipped: false,
+            encoding: "UTF-8".to_string(),
         });
     }
 
     let file = File::open(path).context("Failed to open file")?;
     let mut reader = BufReader::new(file);
     let mut content = Vec::new();
-    
+
     // Read all to check UTF-8 validitity and simplicity.
     // For 2MB max, reading into memory is acceptable and safer for UTF-8 check.
     reader.read_to_end(&mut content).context("Failed to read file content")?;
 
-    match String::from_utf8(content) {
-        Ok(text) => {
-            // Count lines. 
-            // We count lines as number of lines with content, or just newlines?
-            // "Standard" `wc -l` counts newlines.
-            // If the last line has no newline, it might not be counted by some tools.
-            // Let's adopt a standard: count split by '\n'.
-            // Actually, `.lines()` in Rust iterates over lines.
-            // Empty string "" has 0 lines.
-            // "a" has 1 line.
-            // "a\n" has 1 line? or 2?
-            // "a\nb" has 2 lines.
-            
-            // To be strictly deterministic and simple: match `wc -l` semantics usually,
-            // OR match "text editor" lines.
-            // Let's use `lines().count()`.
+    match decode_content(&content) {
+        Some((text, encoding)) => {
             // Rust `lines()` handles `\n` and `\r\n`.
             let loc = text.lines().count() as u64;
-            
-            // Re-verify edge case:
-            // "a\n" -> lines() yields ["a"]. count = 1.
-            // "a"   -> lines() yields ["a"]. count = 1.
-            // ""    -> lines() yields []. count = 0.
-            // This seems reasonable for "Loc".
-            
+            let syntax = language::comment_syntax(lang);
+            let (code, comments, blanks) = classify_lines(&text, &syntax);
+
             Ok(LocStats {
                 loc,
+                code,
+                comments,
+                blanks,
                 size,
                 skipped: false,
+                encoding: encoding.to_string(),
             })
         }
-        Err(_) => {
-            // Invalid UTF-8
+        None => {
+            // Genuinely binary: no BOM and not valid UTF-8, and it contains a NUL byte.
             Ok(LocStats {
                 loc: 0,
+                code: 0,
+                comments: 0,
+                blanks: 0,
                 size,
                 skipped: true,
+                encoding: "binary".to_string(),
             })
         }
     }
 }
 
+/// Decodes raw file bytes to text, returning `(text, encoding_label)`, or `None` if the
+/// content is genuinely binary.
+///
+/// Detection order:
+/// 1. A UTF-8/UTF-16LE/UTF-16BE byte-order mark, decoded accordingly.
+/// 2. Valid UTF-8 with no BOM.
+/// 3. A lossy Latin-1 (Windows-1252) decode, unless the content contains a NUL byte, in
+///    which case it's treated as binary and `None` is returned.
+fn decode_content(content: &[u8]) -> Option<(String, &'static str)> {
+    if let Some(rest) = content.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return Some((String::from_utf8_lossy(rest).into_owned(), "UTF-8"));
+    }
+    if let Some(rest) = content.strip_prefix(&[0xFF, 0xFE]) {
+        let (text, _, _) = UTF_16LE.decode(rest);
+        return Some((text.into_owned(), "UTF-16LE"));
+    }
+    if let Some(rest) = content.strip_prefix(&[0xFE, 0xFF]) {
+        let (text, _, _) = UTF_16BE.decode(rest);
+        return Some((text.into_owned(), "UTF-16BE"));
+    }
+
+    if let Ok(text) = std::str::from_utf8(content) {
+        return Some((text.to_string(), "UTF-8"));
+    }
+
+    if content.contains(&0u8) {
+        return None;
+    }
+
+    let (text, _, _) = WINDOWS_1252.decode(content);
+    Some((text.into_owned(), "Latin-1"))
+}
+
+/// Classifies every line of `text` as code, comment, or blank, returning
+/// `(code, comments, blanks)` counts.
+///
+/// A line is blank if it is empty after trimming whitespace. Otherwise it is walked
+/// character by character tracking whether we are inside a string literal (respecting
+/// backslash escapes) or inside a nested block comment (depth carried across line
+/// boundaries). Comment-opening tokens found inside a string literal are ignored. A
+/// line counts as a comment only if every non-blank character on it lies inside a
+/// comment range; if any character is code, the whole line counts as code.
+fn classify_lines(text: &str, syntax: &CommentSyntax) -> (u64, u64, u64) {
+    let mut code = 0u64;
+    let mut comments = 0u64;
+    let mut blanks = 0u64;
+    let mut block_depth: u32 = 0;
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            blanks += 1;
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        let mut in_string: Option<char> = None;
+        let mut has_code = false;
+        let mut has_comment = block_depth > 0;
+
+        while i < chars.len() {
+            if let Some(quote) = in_string {
+                if chars[i] == '\\' {
+                    i += 2;
+                } else {
+                    if chars[i] == quote {
+                        in_string = None;
+                    }
+                    i += 1;
+                }
+                has_code = true;
+                continue;
+            }
+
+            if block_depth > 0 {
+                if let Some((open, _)) = syntax
+                    .block_comments
+                    .iter()
+                    .find(|(open, _)| starts_with_at(&chars, i, open))
+                {
+                    block_depth += 1;
+                    i += open.chars().count();
+                } else if let Some((_, close)) = syntax
+                    .block_comments
+                    .iter()
+                    .find(|(_, close)| starts_with_at(&chars, i, close))
+                {
+                    block_depth -= 1;
+                    i += close.chars().count();
+                } else {
+                    i += 1;
+                }
+                has_comment = true;
+                continue;
+            }
+
+            if let Some(&quote) = syntax.quotes.iter().find(|&&q| chars[i] == q) {
+                in_string = Some(quote);
+                has_code = true;
+                i += 1;
+                continue;
+            }
+
+            if let Some((open, _)) = syntax
+                .block_comments
+                .iter()
+                .find(|(open, _)| starts_with_at(&chars, i, open))
+            {
+                block_depth += 1;
+                has_comment = true;
+                i += open.chars().count();
+                continue;
+            }
+
+            if syntax
+                .line_comments
+                .iter()
+                .any(|tok| starts_with_at(&chars, i, tok))
+            {
+                has_comment = true;
+                break;
+            }
+
+            if chars[i].is_whitespace() {
+                i += 1;
+                continue;
+            }
+
+            has_code = true;
+            i += 1;
+        }
+
+        if has_code {
+            code += 1;
+        } else if has_comment {
+            comments += 1;
+        } else {
+            code += 1;
+        }
+    }
+
+    (code, comments, blanks)
+}
+
+/// Returns true if `token` occurs in `chars` starting at index `i`.
+fn starts_with_at(chars: &[char], i: usize, token: &str) -> bool {
+    let mut t = token.chars();
+    let mut c = chars[i..].iter();
+    loop {
+        match (t.next(), c.next()) {
+            (Some(tc), Some(&cc)) if tc == cc => continue,
+            (None, _) => return true,
+            _ => return false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,7 +266,7 @@ mod tests {
     #[test]
     fn test_empty_file() {
         let file = NamedTempFile::new().unwrap();
-        let stats = compute_loc(file.path()).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert_eq!(stats.loc, 0);
         assert!(!stats.skipped);
     }
@@ -107,15 +276,16 @@ mod tests {
         let mut file = NamedTempFile::new().unwrap();
         writeln!(file, "line1").unwrap();
         writeln!(file, "line2").unwrap();
-        let stats = compute_loc(file.path()).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert_eq!(stats.loc, 2);
+        assert_eq!(stats.code, 2);
     }
 
     #[test]
     fn test_no_trailing_newline() {
         let mut file = NamedTempFile::new().unwrap();
         write!(file, "line1\nline2").unwrap(); // 2 lines
-        let stats = compute_loc(file.path()).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert_eq!(stats.loc, 2);
     }
 
@@ -123,7 +293,7 @@ mod tests {
     fn test_single_line_no_newline() {
          let mut file = NamedTempFile::new().unwrap();
         write!(file, "line1").unwrap(); // 1 line
-        let stats = compute_loc(file.path()).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert_eq!(stats.loc, 1);
     }
 
@@ -131,7 +301,7 @@ mod tests {
     fn test_crlf_normalization() {
         let mut file = NamedTempFile::new().unwrap();
         write!(file, "line1\r\nline2").unwrap();
-        let stats = compute_loc(file.path()).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert_eq!(stats.loc, 2);
     }
 
@@ -139,8 +309,8 @@ mod tests {
     fn test_binary_skipped() {
         let mut file = NamedTempFile::new().unwrap();
         // invalid utf8 sequence
-        file.write_all(&[0, 159, 146, 150]).unwrap(); 
-        let stats = compute_loc(file.path()).unwrap();
+        file.write_all(&[0, 159, 146, 150]).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert!(stats.skipped);
         assert_eq!(stats.loc, 0);
     }
@@ -150,8 +320,79 @@ mod tests {
         let mut file = NamedTempFile::new().unwrap();
         let big_data = vec![b'a'; (LOC_BIG_FILE_CAP_BYTES + 1) as usize];
         file.write_all(&big_data).unwrap();
-        let stats = compute_loc(file.path()).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
         assert!(stats.skipped);
         assert_eq!(stats.loc, 0);
     }
+
+    #[test]
+    fn test_rust_block_comment_and_code_classification() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "// a line comment").unwrap();
+        writeln!(file, "let s = \"a // not a comment\";").unwrap();
+        writeln!(file, "/* start").unwrap();
+        writeln!(file, "still a comment").unwrap();
+        writeln!(file, "end */").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "let x = 1;").unwrap();
+        let stats = compute_loc(file.path(), "Rust").unwrap();
+        assert_eq!(stats.comments, 4);
+        assert_eq!(stats.code, 2);
+        assert_eq!(stats.blanks, 1);
+    }
+
+    #[test]
+    fn test_rust_nested_block_comment() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "/* /* */ */").unwrap();
+        let stats = compute_loc(file.path(), "Rust").unwrap();
+        assert_eq!(stats.comments, 1);
+        assert_eq!(stats.code, 0);
+    }
+
+    #[test]
+    fn test_indented_comment_is_not_counted_as_code() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "fn main() {{").unwrap();
+        writeln!(file, "    // indented comment").unwrap();
+        writeln!(file, "    // another indented comment").unwrap();
+        writeln!(file, "}}").unwrap();
+        let stats = compute_loc(file.path(), "Rust").unwrap();
+        assert_eq!(stats.comments, 2);
+        assert_eq!(stats.code, 2);
+    }
+
+    #[test]
+    fn test_utf16_le_transcoded() {
+        let mut file = NamedTempFile::new().unwrap();
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "line1\nline2".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        file.write_all(&bytes).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
+        assert!(!stats.skipped);
+        assert_eq!(stats.encoding, "UTF-16LE");
+        assert_eq!(stats.loc, 2);
+    }
+
+    #[test]
+    fn test_latin1_fallback() {
+        let mut file = NamedTempFile::new().unwrap();
+        // 0xE9 is "é" in Latin-1/Windows-1252, but not valid standalone UTF-8.
+        file.write_all(b"caf\xE9\n").unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
+        assert!(!stats.skipped);
+        assert_eq!(stats.encoding, "Latin-1");
+        assert_eq!(stats.loc, 1);
+    }
+
+    #[test]
+    fn test_nul_bytes_still_binary() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&[0, 159, 146, 150]).unwrap();
+        let stats = compute_loc(file.path(), "Unknown").unwrap();
+        assert!(stats.skipped);
+        assert_eq!(stats.encoding, "binary");
+    }
 }
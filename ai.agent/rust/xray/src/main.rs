@@ -10,6 +10,8 @@ mod loc;
 mod traversal;
 mod hash;
 mod language;
+#[cfg(feature = "complexity")]
+mod complexity;
 
 #[derive(Parser)]
 #[command(name = "xray")]
@@ -73,7 +75,7 @@ fn run_scan(target: &str, output: Option<String>) -> Result<()> {
     
     // 3. Compute digest
     let digest_str = digest::calculate_digest(&index)?;
-    index.digest = digest_str;
+    index.index_digest = digest_str;
 
     // 4. Serialize
     let bytes = canonical::to_canonical_json(&index)?;
@@ -90,7 +92,7 @@ fn run_scan(target: &str, output: Option<String>) -> Result<()> {
     // 6. Write
     write::write_atomic(&out_file, &bytes)?;
     
-    println!("XRAY scan complete. Digest: {}", index.digest);
+    println!("XRAY scan complete. Digest: {}", index.index_digest);
     println!("Written to: {}", out_file.display());
 
     Ok(())
@@ -3,7 +3,11 @@ use std::collections::BTreeMap;
 
 /// The authoritative file index.
 /// MUST be Canonical JSON (keys sorted, no whitespace).
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// `Eq` is only derived without the `complexity` feature: `FileNode::complexity_metrics`
+/// carries `f64` fields, which aren't `Eq`.
+#[cfg_attr(not(feature = "complexity"), derive(Eq))]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct XrayIndex {
     /// Schema version (e.g. "1.0.0")
@@ -30,11 +34,13 @@ pub struct XrayIndex {
     /// Aggregate statistics.
     pub stats: RepoStats,
 
-    /// SHA-256 digest of the content (excluding this field).
-    pub digest: String,
+    /// "sha256:<hex>" digest of the whole index's canonical JSON, computed with this field
+    /// set to the empty string.
+    pub index_digest: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[cfg_attr(not(feature = "complexity"), derive(Eq))]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileNode {
     /// Relative path from repo root.
@@ -52,8 +58,33 @@ pub struct FileNode {
     /// Lines of code.
     pub loc: u64,
 
+    /// Lines classified as code (neither a comment nor blank).
+    pub code: u64,
+
+    /// Lines classified as entirely comment.
+    pub comments: u64,
+
+    /// Lines that are empty after trimming whitespace.
+    pub blanks: u64,
+
     /// Calculated complexity score.
     pub complexity: u64,
+
+    /// Encoding the file content was decoded as (e.g. "UTF-8", "UTF-16LE", "Latin-1"),
+    /// or "binary"/"unknown" for a skipped file. Lets downstream consumers tell a
+    /// transcoded file apart from a plain UTF-8 one.
+    pub encoding: String,
+
+    /// SHA-256 digest of this node's own canonical JSON (with `content_hash` itself
+    /// cleared), so downstream caches can detect a per-file change without comparing
+    /// the whole index.
+    pub content_hash: String,
+
+    /// Detailed complexity metrics (cyclomatic, cognitive, Halstead, maintainability
+    /// index). Only present when built with the `complexity` feature; `None` when the
+    /// file's language has no tree-sitter grammar wired up.
+    #[cfg(feature = "complexity")]
+    pub complexity_metrics: Option<crate::complexity::ComplexityStats>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -77,7 +108,7 @@ impl Default for XrayIndex {
                 file_count: 0,
                 total_size: 0,
             },
-            digest: "".to_string(),
+            index_digest: "".to_string(),
         }
     }
 }
@@ -75,12 +75,25 @@ pub fn scan_target(target: &Path) -> Result<ScanResult> {
             path_str
         };
 
+        // Detect Language (Phase C1) - done before LOC so line classification is language-aware.
+        // Only peek the first line when extension/filename detection can't resolve a
+        // language, to avoid a second file open on the common path. Peeking lets
+        // extensionless scripts (e.g. a `#!/usr/bin/env python3` shebang) resolve to a
+        // language instead of falling through to "Unknown".
+        let extension_lang = crate::language::detect_language(path);
+        let lang = if extension_lang == "Unknown" {
+            let first_line = peek_first_line(path);
+            crate::language::detect_language_with_contents(path, first_line.as_deref())
+        } else {
+            extension_lang
+        };
+
         // Compute LOC
-        let loc_stats = loc::compute_loc(path)?;
-        
-        // If skipped (e.g. invalid UTF8 or too big), we currently INCLUDE it in the index 
+        let loc_stats = loc::compute_loc(path, &lang)?;
+
+        // If skipped (e.g. invalid UTF8 or too big), we currently INCLUDE it in the index
         // with 0 LOC, or do we exclude it?
-        // The spec implies index tracks all files. 
+        // The spec implies index tracks all files.
         // Xray schema has "loc" field.
         // Contracts say: "If size > cap: loc = 0 and count as skipped"
         // So we include it.
@@ -90,9 +103,6 @@ pub fn scan_target(target: &Path) -> Result<ScanResult> {
         // Compute Hash (Phase B)
         let hash = crate::hash::compute_file_hash(path).unwrap_or_else(|_| "".to_string());
 
-        // Detect Language (Phase C1)
-        let lang = crate::language::detect_language(path);
-        
         // Aggregate Language
         if lang != "Unknown" {
             *languages.entry(lang.clone()).or_insert(0) += 1;
@@ -132,14 +142,27 @@ pub fn scan_target(target: &Path) -> Result<ScanResult> {
         // So it won't be in module_files unless I add a special check outside the loop.
         // I'll stick to loop for now.
 
-        files.push(FileNode {
+        #[cfg(feature = "complexity")]
+        let complexity_metrics = crate::complexity::compute_complexity(path, &lang, loc_stats.loc);
+
+        let mut node = FileNode {
             path: clean_path,
             size: loc_stats.size,
             hash,
             lang,
             loc: loc_stats.loc,
+            code: loc_stats.code,
+            comments: loc_stats.comments,
+            blanks: loc_stats.blanks,
             complexity: 0,        // Placeholder Phase A
-        });
+            encoding: loc_stats.encoding,
+            content_hash: String::new(),
+            #[cfg(feature = "complexity")]
+            complexity_metrics,
+        };
+        node.content_hash = crate::hash::compute_content_hash(&node).unwrap_or_default();
+
+        files.push(node);
     }
 
     // DETERMINISM: Sort by path
@@ -158,6 +181,21 @@ pub fn scan_target(target: &Path) -> Result<ScanResult> {
     })
 }
 
+/// Reads the first line of `path` (including its terminator, if any), for shebang sniffing.
+/// Returns `None` if the file can't be opened or its first line isn't valid UTF-8.
+fn peek_first_line(path: &Path) -> Option<String> {
+    use std::io::BufRead;
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut line = String::new();
+    std::io::BufReader::new(file).read_line(&mut line).ok()?;
+    if line.is_empty() {
+        None
+    } else {
+        Some(line)
+    }
+}
+
 trait ToSlash {
     fn to_slash_lossy(&self) -> String;
 }